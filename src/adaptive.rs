@@ -0,0 +1,338 @@
+//! Adaptive (FGK) Huffman coding: encoder and decoder each maintain the
+//! same evolving tree as symbols are seen, so no frequency table or
+//! serialized tree needs to be transmitted alongside the payload.
+
+use bit_vec::BitVec;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Encode `content` with a single pass, growing the model as it goes.
+/// The end of the stream is marked by an implicit end-of-input symbol,
+/// so no length needs to be stored separately.
+pub fn encode(content: &[u8]) -> Vec<u8> {
+    let mut tree = Tree::new();
+    let mut bits = BitVec::new();
+
+    for &symbol in content {
+        encode_symbol(&mut tree, Some(symbol), &mut bits);
+    }
+    encode_symbol(&mut tree, None, &mut bits);
+
+    bits.to_bytes()
+}
+
+/// Decode a stream produced by `encode`, rebuilding the identical
+/// evolving tree the encoder used.
+pub fn decode(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut tree = Tree::new();
+    let mut bits = BitVec::from_bytes(encoded).into_iter();
+    let mut out = Vec::new();
+
+    loop {
+        let symbol = decode_symbol(&mut tree, &mut bits)?;
+        match symbol {
+            None => return Some(out),
+            Some(byte) => {
+                out.push(byte);
+                tree.update(symbol);
+            }
+        }
+    }
+}
+
+fn encode_symbol(tree: &mut Tree, symbol: Option<u8>, bits: &mut BitVec) {
+    match tree.leaf_index.get(&symbol) {
+        Some(&leaf) => bits.extend(tree.path_to(leaf)),
+        None => {
+            bits.extend(tree.path_to(tree.nyt));
+            if let Some(byte) = symbol {
+                for i in (0..8).rev() {
+                    bits.push((byte >> i) & 1 == 1);
+                }
+            }
+        }
+    }
+    tree.update(symbol);
+}
+
+fn decode_symbol(tree: &mut Tree, bits: &mut Iterator<Item = bool>) -> Option<Option<u8>> {
+    let mut index = tree.root();
+
+    loop {
+        match &tree.nodes[index as usize].kind {
+            NodeKind::Leaf(symbol) => return Some(*symbol),
+            NodeKind::Nyt => {
+                let mut byte = 0u8;
+                for _ in 0..8 {
+                    byte = (byte << 1) | (bits.next()? as u8);
+                }
+                return Some(Some(byte));
+            }
+            NodeKind::Internal { left, right } => match bits.next()? {
+                true => index = *right,
+                false => index = *left,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    weight: u64,
+    parent: Option<u32>,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    /// The "Not Yet Transmitted" leaf: a symbol not seen before is sent
+    /// as this leaf's path followed by its raw bits.
+    Nyt,
+    Leaf(Option<u8>),
+    Internal { left: u32, right: u32 },
+}
+
+/// The FGK tree, stored as an arena so a "swap two nodes" step (needed to
+/// maintain the sibling property) is just a pointer juggle rather than a
+/// tree rebuild.
+struct Tree {
+    nodes: Vec<Node>,
+    /// Node indices in ascending order of their FGK number: `order[0]` is
+    /// node number 1, and so on. Siblings are adjacent, and weight is
+    /// non-decreasing along the list, so "the highest-numbered node of a
+    /// given weight" is just the topmost matching entry scanning from
+    /// the end.
+    order: Vec<u32>,
+    nyt: u32,
+    leaf_index: HashMap<Option<u8>, u32>,
+}
+
+impl Tree {
+    /// A fresh tree: NYT and the end-of-input leaf under a single root.
+    /// End-of-input is seeded up front (rather than introduced through
+    /// the NYT like any other new symbol) so its first occurrence is
+    /// just a normal known-leaf path, with nothing ambiguous about
+    /// whether raw bits follow.
+    fn new() -> Self {
+        let nodes = vec![
+            Node {
+                weight: 0,
+                parent: Some(2),
+                kind: NodeKind::Nyt,
+            },
+            Node {
+                weight: 0,
+                parent: Some(2),
+                kind: NodeKind::Leaf(None),
+            },
+            Node {
+                weight: 0,
+                parent: None,
+                kind: NodeKind::Internal { left: 0, right: 1 },
+            },
+        ];
+
+        let mut leaf_index = HashMap::new();
+        leaf_index.insert(None, 1);
+
+        Tree {
+            nodes,
+            order: vec![0, 1, 2],
+            nyt: 0,
+            leaf_index,
+        }
+    }
+
+    fn root(&self) -> u32 {
+        let mut index = 0;
+        while let Some(parent) = self.nodes[index as usize].parent {
+            index = parent;
+        }
+        index
+    }
+
+    /// Bits from the root down to `leaf`, `false` for a left branch and
+    /// `true` for a right branch.
+    fn path_to(&self, leaf: u32) -> BitVec {
+        let mut bits = Vec::new();
+        let mut index = leaf;
+
+        while let Some(parent) = self.nodes[index as usize].parent {
+            let went_right = match &self.nodes[parent as usize].kind {
+                NodeKind::Internal { right, .. } => *right == index,
+                _ => unreachable!("a node's parent is always an Internal node"),
+            };
+            bits.push(went_right);
+            index = parent;
+        }
+
+        bits.reverse();
+        BitVec::from_iter(bits)
+    }
+
+    fn push_node(&mut self, node: Node) -> u32 {
+        self.nodes.push(node);
+        (self.nodes.len() - 1) as u32
+    }
+
+    /// Whether `ancestor` lies on `node`'s path to the root.
+    fn is_ancestor(&self, ancestor: u32, mut node: u32) -> bool {
+        while let Some(parent) = self.nodes[node as usize].parent {
+            if parent == ancestor {
+                return true;
+            }
+            node = parent;
+        }
+        false
+    }
+
+    /// The node with the highest FGK number among those with `weight`,
+    /// excluding `node` itself and any of its ancestors or descendants
+    /// (a node is never swapped with anything on its own root path,
+    /// which would otherwise turn the tree into a cycle).
+    fn highest_numbered(&self, weight: u64, node: u32) -> Option<u32> {
+        for &candidate in self.order.iter().rev() {
+            let candidate_weight = self.nodes[candidate as usize].weight;
+            if candidate_weight < weight {
+                return None;
+            }
+            if candidate_weight == weight
+                && candidate != node
+                && !self.is_ancestor(candidate, node)
+                && !self.is_ancestor(node, candidate)
+            {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    /// Swap the tree positions of `a` and `b` (their parent links and
+    /// their slot in `order`), leaving their own weight and subtree
+    /// untouched.
+    fn swap_positions(&mut self, a: u32, b: u32) {
+        let a_parent = self.nodes[a as usize].parent;
+        let b_parent = self.nodes[b as usize].parent;
+
+        if let Some(parent) = a_parent {
+            replace_child(&mut self.nodes[parent as usize], a, b);
+        }
+        if let Some(parent) = b_parent {
+            replace_child(&mut self.nodes[parent as usize], b, a);
+        }
+
+        self.nodes[a as usize].parent = b_parent;
+        self.nodes[b as usize].parent = a_parent;
+
+        let a_pos = self.order.iter().position(|&n| n == a).unwrap();
+        let b_pos = self.order.iter().position(|&n| n == b).unwrap();
+        self.order.swap(a_pos, b_pos);
+    }
+
+    /// Record one more occurrence of `symbol`: split the NYT leaf if
+    /// this is its first occurrence, then walk from the leaf to the
+    /// root, restoring the sibling property before incrementing each
+    /// node's weight.
+    fn update(&mut self, symbol: Option<u8>) {
+        let mut index = match self.leaf_index.get(&symbol) {
+            Some(&leaf) => leaf,
+            None => self.split_nyt(symbol),
+        };
+
+        loop {
+            let weight = self.nodes[index as usize].weight;
+
+            if let Some(top) = self.highest_numbered(weight, index) {
+                if top != index {
+                    self.swap_positions(index, top);
+                }
+            }
+
+            self.nodes[index as usize].weight += 1;
+
+            match self.nodes[index as usize].parent {
+                Some(parent) => index = parent,
+                None => break,
+            }
+        }
+    }
+
+    /// Split the current NYT into an internal node with a fresh NYT on
+    /// the left and a new leaf for `symbol` on the right, renumbering so
+    /// the new NYT is number 1, the new leaf number 2 and the
+    /// now-internal node number 3. Returns the new leaf's index.
+    fn split_nyt(&mut self, symbol: Option<u8>) -> u32 {
+        let old_nyt = self.nyt;
+
+        let new_nyt = self.push_node(Node {
+            weight: 0,
+            parent: Some(old_nyt),
+            kind: NodeKind::Nyt,
+        });
+        let new_leaf = self.push_node(Node {
+            weight: 0,
+            parent: Some(old_nyt),
+            kind: NodeKind::Leaf(symbol),
+        });
+
+        self.nodes[old_nyt as usize].kind = NodeKind::Internal {
+            left: new_nyt,
+            right: new_leaf,
+        };
+
+        let rest = self.order.split_off(1);
+        self.order.clear();
+        self.order.push(new_nyt);
+        self.order.push(new_leaf);
+        self.order.push(old_nyt);
+        self.order.extend(rest);
+
+        self.nyt = new_nyt;
+        self.leaf_index.insert(symbol, new_leaf);
+
+        new_leaf
+    }
+}
+
+fn replace_child(parent: &mut Node, old_child: u32, new_child: u32) {
+    match &mut parent.kind {
+        NodeKind::Internal { left, right } => {
+            if *left == old_child {
+                *left = new_child;
+            } else if *right == old_child {
+                *right = new_child;
+            } else {
+                unreachable!("old_child must be a child of parent");
+            }
+        }
+        _ => unreachable!("parent of a node is always an Internal node"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_empty_input() {
+        assert_eq!(decode(&encode(&[])).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn round_trip_single_byte() {
+        let content = vec![42u8];
+        assert_eq!(decode(&encode(&content)).unwrap(), content);
+    }
+
+    #[test]
+    fn round_trip_repeated_byte() {
+        let content = vec![7u8; 500];
+        assert_eq!(decode(&encode(&content)).unwrap(), content);
+    }
+
+    #[test]
+    fn round_trip_full_byte_range() {
+        let content: Vec<u8> = (0..=255).cycle().take(256 * 3).collect();
+        assert_eq!(decode(&encode(&content)).unwrap(), content);
+    }
+}
@@ -81,6 +81,243 @@ pub enum HuffmanKey<S> {
     },
 }
 
+/// Size of the read buffer used to pull bytes from a `Read` source
+/// without ever holding the whole input in memory at once.
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Encode a `u8` stream in two passes over `open_source`, which must
+/// yield a fresh, equivalent `Read` each time it's called: the first
+/// pass only counts how often each byte occurs, then the key is built, then
+/// the second pass re-reads the source and writes encoded bytes to
+/// `sink` as soon as a full byte of bits has accumulated. Memory use is
+/// bounded by the symbol table and a small buffer, regardless of input
+/// size.
+///
+/// Returns the `HuffmanKey` built from the source, same as `encode`,
+/// since (unlike `encode_to_bytes`) the payload written to `sink` does
+/// not carry it.
+pub fn encode_stream<R, F, W>(mut open_source: F, sink: &mut W) -> std::io::Result<HuffmanKey<u8>>
+where
+    R: std::io::Read,
+    F: FnMut() -> std::io::Result<R>,
+    W: std::io::Write,
+{
+    let mut frequency_map = HashMap::new();
+    let mut buf = [0u8; STREAM_BUFFER_SIZE];
+
+    let mut reader = open_source()?;
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        for &byte in &buf[..read] {
+            *frequency_map.entry(byte).or_insert(0u64) += 1;
+        }
+    }
+
+    let decode_error = std::io::Error::new(std::io::ErrorKind::Other, "Encoding error");
+    let key = key_from_frequency_map(frequency_map).ok_or(decode_error)?;
+    let encode_key = make_encode_key(&key);
+
+    let mut bits = BitVec::new();
+    let mut reader = open_source()?;
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        for &byte in &buf[..read] {
+            bits.extend(encode_key.get(&Some(byte)).expect("symbol missing from encode_key"));
+            flush_full_bytes(&mut bits, sink)?;
+        }
+    }
+
+    bits.extend(
+        encode_key
+            .get(&None)
+            .expect("end of input missing from encode_key"),
+    );
+    flush_full_bytes(&mut bits, sink)?;
+    if !bits.is_empty() {
+        sink.write_all(&bits.to_bytes())?;
+    }
+
+    Ok(key)
+}
+
+/// Write out every complete byte currently buffered in `bits`, leaving
+/// only the trailing, not-yet-full byte behind.
+fn flush_full_bytes<W: std::io::Write>(bits: &mut BitVec, sink: &mut W) -> std::io::Result<()> {
+    let complete_len = (bits.len() / 8) * 8;
+    if complete_len == 0 {
+        return Ok(());
+    }
+
+    let remainder = bits.split_off(complete_len);
+    sink.write_all(&bits.to_bytes())?;
+    *bits = remainder;
+
+    Ok(())
+}
+
+/// Decode a `u8` stream bit by bit from `reader` against `key`, without
+/// ever materializing the full decoded output: each `next()` call reads
+/// only as many bits as it takes to resolve one symbol.
+pub fn decode_stream<R: std::io::Read>(key: &HuffmanKey<u8>, reader: R) -> DecodeStream<'_, R> {
+    DecodeStream {
+        key,
+        reader,
+        buf: BitVec::new(),
+        pos: 0,
+        done: false,
+    }
+}
+
+pub struct DecodeStream<'a, R> {
+    key: &'a HuffmanKey<u8>,
+    reader: R,
+    buf: BitVec,
+    pos: usize,
+    done: bool,
+}
+
+impl<'a, R: std::io::Read> DecodeStream<'a, R> {
+    fn next_bit(&mut self) -> std::io::Result<Option<bool>> {
+        if self.pos >= self.buf.len() {
+            let mut byte = [0u8; 1];
+            if self.reader.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            self.buf = BitVec::from_bytes(&byte);
+            self.pos = 0;
+        }
+
+        let bit = self.buf.get(self.pos);
+        self.pos += 1;
+        Ok(bit)
+    }
+}
+
+impl<'a, R: std::io::Read> Iterator for DecodeStream<'a, R> {
+    type Item = std::io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut tree = self.key;
+        loop {
+            match tree {
+                HuffmanKey::EndOfInput => {
+                    self.done = true;
+                    return None;
+                }
+                HuffmanKey::Symbol(s) => return Some(Ok(*s)),
+                HuffmanKey::Branch { left, right } => match self.next_bit() {
+                    Ok(Some(true)) => tree = right,
+                    Ok(Some(false)) => tree = left,
+                    Ok(None) => {
+                        self.done = true;
+                        return Some(Err(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "truncated huffman stream",
+                        )));
+                    }
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// A cache-friendly alternative to `HuffmanKey`: the same tree shape, but
+/// flattened into a single `Vec<FlatNode<S>>` with `left`/`right` stored
+/// as indices rather than boxed pointers, so decoding walks a contiguous
+/// array instead of chasing allocations.
+#[derive(Debug)]
+pub struct FlatHuffmanKey<S> {
+    nodes: Vec<FlatNode<S>>,
+}
+
+#[derive(Debug)]
+enum FlatNode<S> {
+    EndOfInput,
+    Symbol(S),
+    Branch { left: u32, right: u32 },
+}
+
+impl<S: Copy> FlatHuffmanKey<S> {
+    fn root(&self) -> u32 {
+        (self.nodes.len() - 1) as u32
+    }
+}
+
+impl<S: Copy> From<&HuffmanKey<S>> for FlatHuffmanKey<S> {
+    /// Build a `FlatHuffmanKey` from an existing `HuffmanKey`, so both
+    /// representations can coexist and callers can pick whichever suits
+    /// their decode path.
+    fn from(key: &HuffmanKey<S>) -> Self {
+        let mut nodes = Vec::new();
+        flatten(key, &mut nodes);
+        FlatHuffmanKey { nodes }
+    }
+}
+
+/// Push `key`'s nodes onto `nodes` in post-order (children before their
+/// parent), returning the index of the node just pushed.
+fn flatten<S: Copy>(key: &HuffmanKey<S>, nodes: &mut Vec<FlatNode<S>>) -> u32 {
+    let node = match key {
+        HuffmanKey::EndOfInput => FlatNode::EndOfInput,
+        HuffmanKey::Symbol(s) => FlatNode::Symbol(*s),
+        HuffmanKey::Branch { left, right } => {
+            let left = flatten(left, nodes);
+            let right = flatten(right, nodes);
+            FlatNode::Branch { left, right }
+        }
+    };
+    nodes.push(node);
+    (nodes.len() - 1) as u32
+}
+
+/// Decode `encoded` against a `FlatHuffmanKey`, indexing into its node
+/// array instead of following `Box` pointers through a `HuffmanKey`.
+pub fn decode_flat<S: Copy + Debug>(key: &FlatHuffmanKey<S>, encoded: &[u8]) -> Option<Vec<S>> {
+    let mut bits = BitVec::from_bytes(encoded).into_iter();
+    let mut out = Vec::new();
+
+    loop {
+        match decode_symbol_flat(key, &mut bits) {
+            DecodedSymbol::NotEnoughBits => return None,
+            DecodedSymbol::EndOfInput => return Some(out),
+            DecodedSymbol::Symbol(s) => out.push(s),
+        }
+    }
+}
+
+fn decode_symbol_flat<S: Copy + Debug>(
+    key: &FlatHuffmanKey<S>,
+    bits: &mut Iterator<Item = bool>,
+) -> DecodedSymbol<S> {
+    let mut index = key.root();
+
+    loop {
+        match &key.nodes[index as usize] {
+            FlatNode::EndOfInput => return DecodedSymbol::EndOfInput,
+            FlatNode::Symbol(s) => return DecodedSymbol::Symbol(*s),
+            FlatNode::Branch { left, right } => match bits.next() {
+                None => return DecodedSymbol::NotEnoughBits,
+                Some(true) => index = *right,
+                Some(false) => index = *left,
+            },
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum HuffmanTree<S> {
     Leaf {
@@ -130,7 +367,15 @@ fn frequency<S>(tree: &HuffmanTree<S>) -> u64 {
 
 fn make_key<S: Eq + Hash + Copy + Ord + Debug>(content: &[S]) -> Option<HuffmanKey<S>> {
     let frequency_map = symbol_frequency(&content);
+    key_from_frequency_map(frequency_map)
+}
 
+/// Build a `HuffmanKey` from a pre-accumulated symbol frequency map,
+/// shared by `make_key` and `encode_stream` (which builds the map itself
+/// from a `Read` source instead of an in-memory slice).
+fn key_from_frequency_map<S: Eq + Hash + Copy + Ord + Debug>(
+    frequency_map: HashMap<S, u64>,
+) -> Option<HuffmanKey<S>> {
     let mut priority_queue =
         BinaryHeap::from_iter(frequency_map.iter().map(|(&s, &v)| HuffmanTree::Leaf {
             frequency: v,
@@ -185,6 +430,254 @@ fn tree_to_key<S>(tree: HuffmanTree<Option<S>>) -> HuffmanKey<S> {
     }
 }
 
+/// Magic bytes identifying a serialized huffman container.
+const MAGIC: &[u8; 4] = b"HUF2";
+const VERSION: u8 = 2;
+
+/// Size in bytes of the fixed-size part of the container header (magic +
+/// version + original length + end-of-input code length + symbol count).
+/// Followed by `symbol_count` entries of `(symbol: u8, length: u8)`.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 8 + 1 + 2;
+
+/// Encode `content` and serialize the resulting key and payload into a
+/// single self-contained container: a small header carrying canonical
+/// code lengths (instead of the full tree shape) followed by the encoded
+/// payload.
+///
+/// Unlike `encode`, the container returned here carries everything needed
+/// to decode it back with `decode_from_bytes`, with nothing left to
+/// persist separately.
+pub fn encode_to_bytes(content: &[u8]) -> Option<Vec<u8>> {
+    let key = make_key(content)?;
+    let lengths = code_lengths(&key);
+    let codes = canonical_codes(&lengths)?;
+
+    let mut bits = BitVec::new();
+    for symbol in content {
+        bits.extend(codes.get(&Some(*symbol))?);
+    }
+    bits.extend(codes.get(&None)?);
+
+    let payload = bits.to_bytes();
+
+    let symbol_lengths: Vec<(u8, u8)> = lengths
+        .iter()
+        .filter_map(|(&symbol, &length)| symbol.map(|s| (s, length)))
+        .collect();
+    let end_of_input_length = lengths[&None];
+
+    let mut out = Vec::with_capacity(HEADER_LEN + symbol_lengths.len() * 2 + payload.len());
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&(content.len() as u64).to_le_bytes());
+    out.push(end_of_input_length);
+    out.extend_from_slice(&(symbol_lengths.len() as u16).to_le_bytes());
+    for (symbol, length) in symbol_lengths {
+        out.push(symbol);
+        out.push(length);
+    }
+    out.extend_from_slice(&payload);
+
+    Some(out)
+}
+
+/// Parse a container produced by `encode_to_bytes`, rebuilding the
+/// canonical code assignment from the per-symbol lengths in the header
+/// before decoding the payload.
+///
+/// Returns `None` if the header is missing, the magic bytes or version
+/// don't match, the lengths don't describe a complete code, or the
+/// payload runs out of bits before the content is fully decoded.
+pub fn decode_from_bytes(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < HEADER_LEN || &data[0..4] != MAGIC {
+        return None;
+    }
+    if data[4] != VERSION {
+        return None;
+    }
+    let mut original_length_bytes = [0u8; 8];
+    original_length_bytes.copy_from_slice(&data[5..13]);
+    let original_length = u64::from_le_bytes(original_length_bytes) as usize;
+
+    let end_of_input_length = data[13];
+    let symbol_count = u16::from_le_bytes([data[14], data[15]]) as usize;
+
+    let entries_end = HEADER_LEN + symbol_count * 2;
+    if data.len() < entries_end {
+        return None;
+    }
+
+    let mut lengths = HashMap::with_capacity(symbol_count + 1);
+    lengths.insert(None, end_of_input_length);
+    for entry in data[HEADER_LEN..entries_end].chunks_exact(2) {
+        lengths.insert(Some(entry[0]), entry[1]);
+    }
+
+    let key = key_from_canonical_lengths(&lengths)?;
+
+    let mut bits = BitVec::from_bytes(&data[entries_end..]).into_iter();
+    let mut out = Vec::with_capacity(original_length);
+    loop {
+        match decode_symbol(&key, &mut bits) {
+            DecodedSymbol::NotEnoughBits => return None,
+            DecodedSymbol::EndOfInput => return Some(out),
+            DecodedSymbol::Symbol(s) => out.push(s),
+        }
+    }
+}
+
+/// Walk `key` to record the bit-length (depth) of each symbol, keyed by
+/// `Some(symbol)` or `None` for the end-of-input leaf.
+fn code_lengths<S: Eq + Hash + Copy>(key: &HuffmanKey<S>) -> HashMap<Option<S>, u8> {
+    let mut lengths = HashMap::new();
+    let mut stack = vec![(0u8, key)];
+
+    while let Some((depth, key)) = stack.pop() {
+        match key {
+            HuffmanKey::EndOfInput => {
+                lengths.insert(None, depth);
+            }
+            HuffmanKey::Symbol(symbol) => {
+                lengths.insert(Some(*symbol), depth);
+            }
+            HuffmanKey::Branch { left, right } => {
+                stack.push((depth + 1, left));
+                stack.push((depth + 1, right));
+            }
+        }
+    }
+
+    lengths
+}
+
+/// Assign canonical codes from per-symbol lengths alone: sort symbols by
+/// `(length, symbol)` ascending, give the first the all-zero code of its
+/// length, then for each subsequent symbol compute
+/// `code = (prev_code + 1) << (len_cur - len_prev)`.
+///
+/// Returns `None` if the lengths can't come from any valid prefix code,
+/// e.g. a length table crafted (or corrupted) so a code would need more
+/// bits than its length allows.
+fn canonical_codes<S: Eq + Hash + Copy + Ord>(
+    lengths: &HashMap<Option<S>, u8>,
+) -> Option<HashMap<Option<S>, BitVec>> {
+    let mut symbols: Vec<(Option<S>, u8)> = lengths.iter().map(|(&s, &len)| (s, len)).collect();
+    symbols.sort_by_key(|&(symbol, len)| (len, symbol));
+
+    let mut codes = HashMap::with_capacity(symbols.len());
+    let mut prev_code: u32 = 0;
+    let mut prev_len: u8 = 0;
+
+    for (i, (symbol, len)) in symbols.into_iter().enumerate() {
+        // Codes are tracked in a u32, so lengths beyond 32 bits are simply
+        // not representable here and are rejected (a tree that deep only
+        // comes from millions of symbols with Fibonacci-like frequencies).
+        if len > 32 {
+            return None;
+        }
+
+        let code = if i == 0 {
+            0
+        } else {
+            let shift = len.checked_sub(prev_len)?;
+            prev_code.checked_add(1)?.checked_shl(u32::from(shift))?
+        };
+        if code.checked_shr(u32::from(len)).unwrap_or(0) != 0 {
+            return None;
+        }
+
+        let mut bits = BitVec::from_elem(len as usize, false);
+        for bit in 0..len as usize {
+            bits.set(bit, (code >> (len as usize - 1 - bit)) & 1 == 1);
+        }
+        codes.insert(symbol, bits);
+
+        prev_code = code;
+        prev_len = len;
+    }
+
+    Some(codes)
+}
+
+/// Intermediate tree used to rebuild a `HuffmanKey` from canonical codes,
+/// since a leaf's position is only known once every bit of its code has
+/// been walked.
+enum CanonicalNode<S> {
+    Empty,
+    Leaf(HuffmanKey<S>),
+    Branch(Box<CanonicalNode<S>>, Box<CanonicalNode<S>>),
+}
+
+/// Insert `leaf` at the position its code describes. Returns `None` if the
+/// length table is inconsistent: either this code collides with one
+/// already occupying the same position, or an earlier, shorter code is a
+/// prefix of this one (so the table doesn't describe a valid prefix code).
+fn insert_canonical_code<S>(
+    node: CanonicalNode<S>,
+    bits: &BitVec,
+    depth: usize,
+    leaf: HuffmanKey<S>,
+) -> Option<CanonicalNode<S>> {
+    if depth == bits.len() {
+        return match node {
+            CanonicalNode::Empty => Some(CanonicalNode::Leaf(leaf)),
+            _ => None,
+        };
+    }
+
+    let (left, right) = match node {
+        CanonicalNode::Empty => (CanonicalNode::Empty, CanonicalNode::Empty),
+        CanonicalNode::Branch(left, right) => (*left, *right),
+        CanonicalNode::Leaf(_) => return None,
+    };
+
+    if bits[depth] {
+        Some(CanonicalNode::Branch(
+            Box::new(left),
+            Box::new(insert_canonical_code(right, bits, depth + 1, leaf)?),
+        ))
+    } else {
+        Some(CanonicalNode::Branch(
+            Box::new(insert_canonical_code(left, bits, depth + 1, leaf)?),
+            Box::new(right),
+        ))
+    }
+}
+
+fn canonical_node_to_key<S>(node: CanonicalNode<S>) -> Option<HuffmanKey<S>> {
+    match node {
+        CanonicalNode::Empty => None,
+        CanonicalNode::Leaf(key) => Some(key),
+        CanonicalNode::Branch(left, right) => Some(HuffmanKey::Branch {
+            left: Box::new(canonical_node_to_key(*left)?),
+            right: Box::new(canonical_node_to_key(*right)?),
+        }),
+    }
+}
+
+/// Rebuild the `HuffmanKey` a decoder needs purely from per-symbol code
+/// lengths, by reconstructing the same canonical code assignment the
+/// encoder used and inserting each symbol at its code's position.
+///
+/// Returns `None` if the lengths don't describe a complete, non-colliding
+/// prefix code (e.g. a corrupted header), rather than panicking.
+fn key_from_canonical_lengths<S: Eq + Hash + Copy + Ord>(
+    lengths: &HashMap<Option<S>, u8>,
+) -> Option<HuffmanKey<S>> {
+    let codes = canonical_codes(lengths)?;
+
+    let mut root = CanonicalNode::Empty;
+    for (symbol, bits) in &codes {
+        let leaf = match symbol {
+            Some(s) => HuffmanKey::Symbol(*s),
+            None => HuffmanKey::EndOfInput,
+        };
+        root = insert_canonical_code(root, bits, 0, leaf)?;
+    }
+
+    canonical_node_to_key(root)
+}
+
 fn make_encode_key<S: Eq + Hash + Copy + Debug>(key: &HuffmanKey<S>) -> HashMap<Option<S>, BitVec> {
     let mut map = HashMap::new();
     let mut stack = Vec::new();
@@ -211,3 +704,78 @@ fn make_encode_key<S: Eq + Hash + Copy + Debug>(key: &HuffmanKey<S>) -> HashMap<
     }
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_round_trip() {
+        let content = b"the quick brown fox jumps over the lazy dog".to_vec();
+        let container = encode_to_bytes(&content).unwrap();
+        assert_eq!(decode_from_bytes(&container).unwrap(), content);
+    }
+
+    #[test]
+    fn container_round_trip_empty_input() {
+        let container = encode_to_bytes(&[]).unwrap();
+        assert_eq!(decode_from_bytes(&container).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn container_round_trip_single_distinct_symbol() {
+        let content = vec![b'a'; 100];
+        let container = encode_to_bytes(&content).unwrap();
+        assert_eq!(decode_from_bytes(&container).unwrap(), content);
+    }
+
+    #[test]
+    fn decode_from_bytes_rejects_malformed_header() {
+        let content = b"the quick brown fox jumps over the lazy dog".to_vec();
+        let mut container = encode_to_bytes(&content).unwrap();
+
+        let symbol_count = u16::from_le_bytes([container[14], container[15]]) as usize;
+        let entries_end = HEADER_LEN + symbol_count * 2;
+        // Blow out every code length in the header: 0xff is longer than
+        // any code length the decoder supports, so decoding must fail
+        // cleanly instead of panicking.
+        for length_byte in container[HEADER_LEN..entries_end].iter_mut().skip(1).step_by(2) {
+            *length_byte = 0xff;
+        }
+
+        assert!(decode_from_bytes(&container).is_none());
+    }
+
+    #[test]
+    fn flat_huffman_key_matches_huffman_key_decode() {
+        let content = b"mississippi river".to_vec();
+        let (key, encoded) = encode(&content).unwrap();
+        let flat_key = FlatHuffmanKey::from(&key);
+
+        assert_eq!(decode(&key, &encoded).unwrap(), content);
+        assert_eq!(decode_flat(&flat_key, &encoded).unwrap(), content);
+    }
+
+    #[test]
+    fn stream_round_trip() {
+        let content = b"the quick brown fox jumps over the lazy dog".repeat(100);
+
+        let mut encoded = Vec::new();
+        let key = encode_stream(|| Ok(&content[..]), &mut encoded).unwrap();
+
+        let decoded: std::io::Result<Vec<u8>> = decode_stream(&key, &encoded[..]).collect();
+        assert_eq!(decoded.unwrap(), content);
+    }
+
+    #[test]
+    fn decode_stream_reports_truncated_input() {
+        let content = b"the quick brown fox jumps over the lazy dog".repeat(100);
+
+        let mut encoded = Vec::new();
+        let key = encode_stream(|| Ok(&content[..]), &mut encoded).unwrap();
+        encoded.truncate(encoded.len() / 2);
+
+        let decoded: std::io::Result<Vec<u8>> = decode_stream(&key, &encoded[..]).collect();
+        assert!(decoded.is_err());
+    }
+}
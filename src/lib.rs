@@ -0,0 +1,2 @@
+pub mod adaptive;
+pub mod huffman;
@@ -1,31 +1,85 @@
-mod huffman;
+use huffman::huffman;
 
 use std::env;
 use std::fs::File;
-use std::io::{BufReader, Error, ErrorKind, Read};
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
 
 fn main() -> std::io::Result<()> {
-    // Get the file path from the first command line argument
-    let arg_error = Error::new(ErrorKind::Other, "Not enough command line arguments");
-    let file_path = env::args().nth(1).ok_or(arg_error)?;
+    let usage_error = || {
+        Error::new(
+            ErrorKind::Other,
+            "Usage: huffman <compress|decompress|stats> <input> [output]",
+        )
+    };
 
-    // Read the content of the file
-    let file = File::open(file_path)?;
+    let mut args = env::args().skip(1);
+    let subcommand = args.next().ok_or_else(usage_error)?;
+
+    match subcommand.as_str() {
+        "compress" => {
+            let input_path = args.next().ok_or_else(usage_error)?;
+            let output_path = args.next().ok_or_else(usage_error)?;
+            compress(&input_path, &output_path)
+        }
+        "decompress" => {
+            let input_path = args.next().ok_or_else(usage_error)?;
+            let output_path = args.next().ok_or_else(usage_error)?;
+            decompress(&input_path, &output_path)
+        }
+        "stats" => {
+            let input_path = args.next().ok_or_else(usage_error)?;
+            stats(&input_path)
+        }
+        _ => Err(usage_error()),
+    }
+}
+
+fn read_file(path: &str) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
     let mut buf_reader = BufReader::new(file);
     let mut content = Vec::new();
     buf_reader.read_to_end(&mut content)?;
+    Ok(content)
+}
 
-    // When the text given is empty
-    let content_error = Error::new(ErrorKind::Other, "Encoding error");
-    let (key, encoded) = huffman::encode(&content).ok_or(content_error)?;
-    let decoded = huffman::decode(&key, &encoded);
+/// Read `input_path`, Huffman-encode it and write the self-contained
+/// container (tree lengths + payload) to `output_path`.
+fn compress(input_path: &str, output_path: &str) -> std::io::Result<()> {
+    let content = read_file(input_path)?;
 
-    // println!("encoded: {:?}", encoded);
-    // println!("decoded: {:?}", decoded);
+    let encode_error = Error::new(ErrorKind::Other, "Encoding error");
+    let container = huffman::encode_to_bytes(&content).ok_or(encode_error)?;
+
+    let file = File::create(output_path)?;
+    let mut buf_writer = BufWriter::new(file);
+    buf_writer.write_all(&container)?;
+
+    Ok(())
+}
 
-    // if let Some(Ok(x)) = Option::map(decoded, String::from_utf8) {
-    //     println!("{}", x);
-    // }
+/// Read a container produced by `compress` from `input_path`, decode it
+/// and write the original bytes back out to `output_path`.
+fn decompress(input_path: &str, output_path: &str) -> std::io::Result<()> {
+    let container = read_file(input_path)?;
+
+    let decode_error = Error::new(ErrorKind::Other, "Corrupt or truncated input");
+    let content = huffman::decode_from_bytes(&container).ok_or(decode_error)?;
+
+    let file = File::create(output_path)?;
+    let mut buf_writer = BufWriter::new(file);
+    buf_writer.write_all(&content)?;
+
+    Ok(())
+}
+
+/// Print the size of `input_path`, the size of its `encode` payload (the
+/// key is not counted) and the payload size as a percentage of the input
+/// size, without writing anything out.
+fn stats(input_path: &str) -> std::io::Result<()> {
+    let content = read_file(input_path)?;
+
+    let content_error = Error::new(ErrorKind::Other, "Encoding error");
+    let (_, encoded) = huffman::encode(&content).ok_or(content_error)?;
 
     let content_size = content.len();
     let encoded_size = encoded.len();